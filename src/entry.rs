@@ -1,13 +1,26 @@
-use linked_hash_map;
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
 use std::collections::hash_map::RandomState;
-use std::hash::{Hash, BuildHasher};
+use std::hash::{BuildHasher, Hash};
+use std::time::Instant;
 
+/// A view into a single entry in an `LruCache`, which may be vacant or occupied. See
+/// [`LruCache::entry`](crate::LruCache::entry).
 pub enum Entry<'a, K: 'a + Eq + Hash, V: 'a, S: 'a + BuildHasher = RandomState> {
     Occupied(OccupiedEntry<'a, K, V, S>),
     Vacant(VacantEntry<'a, K, V, S>),
 }
 
 impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> Entry<'a, K, V, S> {
+    /// Gets a reference to the entry's key.
     pub fn key(&self) -> &K {
         match self {
             Entry::Occupied(e) => e.key(),
@@ -15,6 +28,8 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> Entry<'a, K, V, S> {
         }
     }
 
+    /// Ensures the entry has a value by inserting `default` if it was
+    /// vacant, then returns a mutable reference to the value.
     pub fn or_insert(self, default: V) -> &'a mut V {
         match self {
             Entry::Occupied(e) => e.into_mut(),
@@ -22,6 +37,8 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> Entry<'a, K, V, S> {
         }
     }
 
+    /// Ensures the entry has a value by inserting the result of `default`
+    /// if it was vacant, then returns a mutable reference to the value.
     pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
         match self {
             Entry::Occupied(e) => e.into_mut(),
@@ -30,8 +47,12 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> Entry<'a, K, V, S> {
     }
 }
 
-pub struct OccupiedEntry<'a, K: 'a, V: 'a, S: 'a = RandomState> {
-    pub(crate) entry: linked_hash_map::OccupiedEntry<'a, K, V, S>,
+pub struct OccupiedEntry<'a, K: 'a + Eq + Hash, V: 'a, S: 'a = RandomState> {
+    pub(crate) entry: linked_hash_map::OccupiedEntry<'a, K, (Instant, V, usize), S>,
+
+    // See the comment on `VacantEntry::cache`: points at the same cache as
+    // `entry`, and must not be dereferenced while `entry` is alive.
+    pub(crate) cache: *mut crate::LruCache<K, V, S>,
 }
 
 impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> OccupiedEntry<'a, K, V, S> {
@@ -42,34 +63,48 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> OccupiedEntry<'a, K, V,
 
     /// Gets a mutable reference to the value in the entry.
     pub fn get_mut(&mut self) -> &mut V {
-        self.entry.get_mut()
+        &mut self.entry.get_mut().1
     }
 
     /// Converts the OccupiedEntry into a mutable reference to the value in the
     /// entry with a lifetime bound to the map itself.
     pub fn into_mut(self) -> &'a mut V {
-        self.entry.into_mut()
+        &mut self.entry.into_mut().1
     }
 
     /// Sets the value of the entry, and returns the entry's old value.
+    ///
+    /// The entry's size is recomputed under `LruCache::with_memory_limit`,
+    /// same as `LruCache::mutate`, and least-recently-used entries are
+    /// evicted if the new value pushed the total over the limit.
     pub fn insert(&mut self, value: V) -> V {
-        self.entry.insert(value)
-        // Note: This is an overwrite so we don't need to expire anything.
+        let new_size = unsafe { (*self.cache).size_fn }.map_or(0, |f| f(self.entry.key(), &value));
+        let (_, old, old_size) = std::mem::replace(self.entry.get_mut(), (Instant::now(), value, new_size));
+
+        let cache = unsafe { &mut *self.cache };
+        cache.mem_usage = cache.mem_usage - old_size + new_size;
+        if let Some(limit) = cache.mem_limit {
+            while cache.mem_usage > limit && cache.len() > 1 {
+                cache.remove_lru();
+            }
+        }
+        old
     }
 
     /// Takes the value out of the entry, and returns it.
     pub fn remove(self) -> V {
-        self.entry.remove()
+        let (_, v, _) = self.entry.remove();
+        v
     }
 }
 
 pub struct VacantEntry<'a, K: 'a + Eq + Hash, V: 'a, S: 'a + BuildHasher = RandomState> {
-    pub(crate) entry: linked_hash_map::VacantEntry<'a, K, V, S>,
+    pub(crate) entry: linked_hash_map::VacantEntry<'a, K, (Instant, V, usize), S>,
 
     // This field points to the same cache that the above entry points to. In order to satisfy
     // Rust's lifetime requirements we *must not* turn it into a reference until the above field is
     // dead.
-    pub(crate) cache: *mut ::LruCache<K, V, S>,
+    pub(crate) cache: *mut crate::LruCache<K, V, S>,
 }
 
 impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> VacantEntry<'a, K, V, S> {
@@ -81,11 +116,13 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> VacantEntry<'a, K, V, S>
     /// Sets the value of the entry with the VacantEntry's key,
     /// and returns a mutable reference to it
     pub fn insert(self, value: V) -> &'a mut V {
+        let size = unsafe { (*self.cache).size_fn }.map_or(0, |f| f(self.entry.key(), &value));
+
         let v = {
-            let v: &'a mut V = self.entry.insert(value);
+            let tuple: &'a mut (Instant, V, usize) = self.entry.insert((Instant::now(), value, size));
 
             // Convert to pointer so that we can make a mutable reference to the cache.
-            v as *mut V
+            &mut tuple.1 as *mut V
         };
 
         // Ideally we would remove before inserting but this requires
@@ -95,13 +132,19 @@ impl<'a, K: 'a + Hash + Eq, V: 'a, S: 'a + BuildHasher> VacantEntry<'a, K, V, S>
         // So instead we convert everything to pointers to avoid aliasing
         // assumptions then remove the value.
         {
-            let cache = unsafe { &mut*self.cache };
+            let cache = unsafe { &mut *self.cache };
+            cache.mem_usage += size;
+            if let Some(limit) = cache.mem_limit {
+                while cache.mem_usage > limit && cache.len() > 1 {
+                    cache.remove_lru();
+                }
+            }
             if cache.len() > cache.capacity() {
                 cache.remove_lru();
             }
         }
 
-        unsafe { &mut*v }
+        unsafe { &mut *v }
     }
 }
 
@@ -186,7 +229,7 @@ mod tests {
         assert_eq!(cache.get_mut(&2), Some(&mut 21));
 
         let old = match cache.entry(2) {
-            Entry::Occupied(mut e) => e.remove(),
+            Entry::Occupied(e) => e.remove(),
             _ => unreachable!("Entry should exist."),
         };
         assert_eq!(old, 21);
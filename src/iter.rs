@@ -0,0 +1,96 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Iterators over an [`LruCache`](crate::LruCache)'s entries, in
+//! most-recently-used to least-recently-used order. Iterating does not
+//! count as a "use", so it never refreshes LRU order.
+
+use std::iter::Rev;
+use std::time::Instant;
+
+use linked_hash_map as lhm;
+
+/// An iterator over `(&K, &V)` entries of an `LruCache`, from
+/// most-recently-used to least-recently-used. See [`LruCache::iter`](crate::LruCache::iter).
+pub struct Iter<'a, K: 'a, V: 'a> {
+    pub(crate) inner: Rev<lhm::Iter<'a, K, (Instant, V, usize)>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, (_, v, _))| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, (_, v, _))| (k, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+/// An iterator over `(&K, &mut V)` entries of an `LruCache`, from
+/// most-recently-used to least-recently-used. See [`LruCache::iter_mut`](crate::LruCache::iter_mut).
+pub struct IterMut<'a, K: 'a, V: 'a> {
+    pub(crate) inner: Rev<lhm::IterMut<'a, K, (Instant, V, usize)>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, &mut (_, ref mut v, _))| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, &mut (_, ref mut v, _))| (k, v))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+/// An owning iterator over `(K, V)` entries of an `LruCache`, from
+/// most-recently-used to least-recently-used. See `IntoIterator` for `LruCache`.
+pub struct IntoIter<K, V> {
+    pub(crate) inner: Rev<lhm::IntoIter<K, (Instant, V, usize)>>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, (_, v, _))| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, (_, v, _))| (k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
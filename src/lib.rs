@@ -13,11 +13,18 @@
 //! (where "used" means a look-up or putting the pair into the cache)
 //! pair is automatically removed.
 //!
+//! A cache can also be created with an expiry duration, in which case an
+//! entry is additionally removed once it has been in the cache for that
+//! long, regardless of capacity -- a look-up does not push the deadline
+//! back. Alternatively, a cache can be bounded by estimated memory usage
+//! instead of entry count via [`LruCache::with_memory_limit`].
+//!
+//! [`LruCache::peek`], [`LruCache::peek_mut`], [`LruCache::contains_key`]
+//! and iteration are look-ups that do not count as a use.
+//!
 //! # Examples
 //!
 //! ```
-//! # extern crate "lru-cache" as lru_cache;
-//! # fn main() {
 //! use lru_cache::LruCache;
 //!
 //! let mut cache = LruCache::new(2);
@@ -37,43 +44,173 @@
 //!
 //! cache.set_capacity(1);
 //! assert!(cache.get(&2).is_none());
-//! # }
 //! ```
 
-extern crate "linked-hash-map" as linked_hash_map;
-
+use std::collections::hash_map::RandomState;
 use std::fmt;
-use std::hash::Hash;
-use std::iter::IntoIterator;
+use std::hash::{BuildHasher, Hash};
+use std::time::{Duration, Instant};
 
 use linked_hash_map::LinkedHashMap;
 
-// FIXME(conventions): implement iterators?
+mod entry;
+mod iter;
+mod mem_size;
+mod two_queue;
+
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use iter::{IntoIter, Iter, IterMut};
+pub use mem_size::MemSize;
+pub use two_queue::TwoQueueCache;
+
 // FIXME(conventions): implement indexing?
 
 /// An LRU cache.
-#[derive(Clone)]
-pub struct LruCache<K, V> where K: Eq + Hash {
-    map: LinkedHashMap<K, V>,
+pub struct LruCache<K, V, S = RandomState> where K: Eq + Hash {
+    map: LinkedHashMap<K, (Instant, V, usize), S>,
     max_size: usize,
+    expiry_duration: Option<Duration>,
+    pub(crate) mem_limit: Option<usize>,
+    pub(crate) mem_usage: usize,
+    pub(crate) size_fn: Option<fn(&K, &V) -> usize>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, S: BuildHasher + Clone> Clone for LruCache<K, V, S> {
+    fn clone(&self) -> Self {
+        LruCache {
+            map: self.map.clone(),
+            max_size: self.max_size,
+            expiry_duration: self.expiry_duration,
+            mem_limit: self.mem_limit,
+            mem_usage: self.mem_usage,
+            size_fn: self.size_fn,
+        }
+    }
 }
 
-impl<K: Hash + Eq, V> LruCache<K, V> {
+impl<K: Hash + Eq, V> LruCache<K, V, RandomState> {
     /// Creates an empty cache that can hold at most `capacity` items.
     ///
     /// # Examples
     ///
     /// ```
-    /// # extern crate "lru-cache" as lru_cache;
-    /// # fn main() {
     /// use lru_cache::LruCache;
     /// let mut cache: LruCache<i32, &str> = LruCache::new(10);
-    /// # }
     /// ```
-    pub fn new(capacity: usize) -> LruCache<K, V> {
+    pub fn new(capacity: usize) -> LruCache<K, V, RandomState> {
+        LruCache {
+            map: LinkedHashMap::new(),
+            max_size: capacity,
+            expiry_duration: None,
+            mem_limit: None,
+            mem_usage: 0,
+            size_fn: None,
+        }
+    }
+
+    /// Creates an empty cache that can hold at most `capacity` items, and
+    /// additionally expires entries `duration` after they were inserted.
+    ///
+    /// An entry is removed once it is either the least-recently-used entry
+    /// past capacity, or older than `duration` -- whichever happens first.
+    /// Looking an entry up does not reset its expiry deadline, only its LRU
+    /// position. Expiry is checked lazily on access (see [`LruCache::get`]),
+    /// so call [`LruCache::remove_expired`] to proactively reclaim memory
+    /// held by stale entries that haven't been looked up recently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use lru_cache::LruCache;
+    /// let mut cache: LruCache<i32, &str> = LruCache::with_expiry_duration(10, Duration::from_secs(60));
+    /// ```
+    pub fn with_expiry_duration(capacity: usize, duration: Duration) -> LruCache<K, V, RandomState> {
         LruCache {
             map: LinkedHashMap::new(),
             max_size: capacity,
+            expiry_duration: Some(duration),
+            mem_limit: None,
+            mem_usage: 0,
+            size_fn: None,
+        }
+    }
+
+    /// Creates an empty cache bounded by estimated memory usage rather than
+    /// entry count: `insert` evicts least-recently-used entries until the
+    /// summed [`MemSize::mem_size`] of every live key and value is back
+    /// under `max_bytes`. The last remaining entry is never evicted, so a
+    /// single entry larger than `max_bytes` stays and the total then
+    /// exceeds the limit.
+    ///
+    /// Mutating a cached value in place can change its size, so updates
+    /// made under this mode should go through [`LruCache::mutate`] rather
+    /// than `get_mut`, so the running total stays accurate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    /// let mut cache: LruCache<i32, i32> = LruCache::with_memory_limit(1024);
+    /// ```
+    pub fn with_memory_limit(max_bytes: usize) -> LruCache<K, V, RandomState> where K: MemSize, V: MemSize {
+        LruCache {
+            map: LinkedHashMap::new(),
+            max_size: usize::MAX,
+            expiry_duration: None,
+            mem_limit: Some(max_bytes),
+            mem_usage: 0,
+            size_fn: Some(|k, v| k.mem_size() + v.mem_size()),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> LruCache<K, V, S> {
+    /// Creates an empty cache that can hold at most `capacity` items, using
+    /// `hash_builder` to hash keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::RandomState;
+    /// use lru_cache::LruCache;
+    /// let mut cache: LruCache<i32, &str> = LruCache::with_hasher(10, RandomState::new());
+    /// ```
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> LruCache<K, V, S> {
+        LruCache {
+            map: LinkedHashMap::with_hasher(hash_builder),
+            max_size: capacity,
+            expiry_duration: None,
+            mem_limit: None,
+            mem_usage: 0,
+            size_fn: None,
+        }
+    }
+
+    /// Creates an empty cache that can hold at most `capacity` items, using
+    /// `hash_builder` to hash keys. An alias for [`LruCache::with_hasher`]
+    /// kept for parity with `std::collections::HashMap`'s constructor names.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> LruCache<K, V, S> {
+        LruCache::with_hasher(capacity, hash_builder)
+    }
+
+    /// Gets the given key's corresponding entry in the cache for in-place
+    /// manipulation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// *cache.entry(1).or_insert(10) += 1;
+    /// assert_eq!(cache.get(&1), Some(&11));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let cache: *mut Self = self;
+        match self.map.entry(key) {
+            linked_hash_map::Entry::Occupied(entry) => Entry::Occupied(OccupiedEntry { entry, cache }),
+            linked_hash_map::Entry::Vacant(entry) => Entry::Vacant(VacantEntry { entry, cache }),
         }
     }
 
@@ -83,8 +220,6 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     /// # Examples
     ///
     /// ```
-    /// # extern crate "lru-cache" as lru_cache;
-    /// # fn main() {
     /// use lru_cache::LruCache;
     ///
     /// let mut cache = LruCache::new(2);
@@ -93,23 +228,81 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     /// cache.insert(2, "b");
     /// assert_eq!(cache.get(&1), Some(&"a"));
     /// assert_eq!(cache.get(&2), Some(&"b"));
-    /// # }
     /// ```
     pub fn insert(&mut self, k: K, v: V) -> Option<V> {
-        let old_val = self.map.insert(k, v);
+        let size = self.size_fn.map_or(0, |f| f(&k, &v));
+        let old_val = self.map.insert(k, (Instant::now(), v, size)).map(|(_, v, old_size)| {
+            self.mem_usage -= old_size;
+            v
+        });
+        self.mem_usage += size;
+
+        if self.mem_limit.is_some() {
+            while self.mem_usage > self.mem_limit.unwrap() && self.len() > 1 {
+                self.remove_lru();
+            }
+        }
         if self.len() > self.capacity() {
             self.remove_lru();
         }
         old_val
     }
 
-    /// Returns the value corresponding to the given key in the cache.
+    /// Applies `f` to the value stored under `k`, if present, refreshing
+    /// its LRU position. If the cache is memory-bounded (see
+    /// [`LruCache::with_memory_limit`]), the entry's size is recomputed
+    /// after `f` runs and least-recently-used entries are evicted until the
+    /// total is back under the limit; the mutated entry itself is never
+    /// evicted. Does nothing if `k` is not present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::with_memory_limit(1024);
+    /// cache.insert("greeting".to_string(), "hi".to_string());
+    /// cache.mutate(&"greeting".to_string(), |v| v.push_str(" there"));
+    /// assert_eq!(cache.get(&"greeting".to_string()), Some(&"hi there".to_string()));
+    /// ```
+    pub fn mutate<F: FnOnce(&mut V)>(&mut self, k: &K, f: F) {
+        // Refresh the LRU position first, then take a mutable handle to the
+        // (now-refreshed) entry to apply `f`.
+        self.map.get_refresh(k);
+        let size_fn = self.size_fn;
+        match self.map.get_mut(k) {
+            Some(&mut (_, ref mut v, ref mut size)) => {
+                f(v);
+                if let Some(size_fn) = size_fn {
+                    let new_size = size_fn(k, v);
+                    self.mem_usage = self.mem_usage - *size + new_size;
+                    *size = new_size;
+                }
+            }
+            None => return,
+        }
+
+        if self.mem_limit.is_some() {
+            while self.mem_usage > self.mem_limit.unwrap() && self.len() > 1 {
+                self.remove_lru();
+            }
+        }
+    }
+
+    /// Returns `true` if `inserted` is older than the cache's expiry duration, if any.
+    fn is_expired(&self, inserted: &Instant) -> bool {
+        match self.expiry_duration {
+            Some(duration) => inserted.elapsed() > duration,
+            None => false,
+        }
+    }
+
+    /// Returns the value corresponding to the given key in the cache, or
+    /// `None` if it's not present or has expired.
     ///
     /// # Examples
     ///
     /// ```
-    /// # extern crate "lru-cache" as lru_cache;
-    /// # fn main() {
     /// use lru_cache::LruCache;
     ///
     /// let mut cache = LruCache::new(2);
@@ -121,10 +314,184 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     ///
     /// assert_eq!(cache.get(&1), None);
     /// assert_eq!(cache.get(&2), Some(&"c"));
-    /// # }
     /// ```
     pub fn get(&mut self, k: &K) -> Option<&V> {
-        self.map.get_refresh(k)
+        let expired = match self.map.get(k) {
+            Some((inserted, _, _)) => self.is_expired(inserted),
+            None => return None,
+        };
+        if expired {
+            self.remove(k);
+            return None;
+        }
+        self.map.get_refresh(k).map(|&mut (_, ref v, _)| v)
+    }
+
+    /// Returns `true` if the cache contains a value for the given key that
+    /// has not expired, without refreshing its LRU position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.insert(1, "a");
+    /// assert!(cache.contains_key(&1));
+    /// assert!(!cache.contains_key(&2));
+    /// ```
+    pub fn contains_key(&self, k: &K) -> bool {
+        match self.map.get(k) {
+            Some((inserted, _, _)) => !self.is_expired(inserted),
+            None => false,
+        }
+    }
+
+    /// Returns the value corresponding to the given key without refreshing
+    /// its LRU position, or `None` if it's not present or has expired.
+    ///
+    /// Use this over [`LruCache::get`] when you only want to inspect a
+    /// value and don't want the look-up itself to count as a use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.insert(1, "a");
+    /// cache.insert(2, "b");
+    /// assert_eq!(cache.peek(&1), Some(&"a"));
+    /// cache.insert(3, "c");
+    /// // Peeking didn't refresh 1, so it was still the LRU entry.
+    /// assert_eq!(cache.peek(&1), None);
+    /// ```
+    pub fn peek(&self, k: &K) -> Option<&V> {
+        match self.map.get(k) {
+            Some((inserted, v, _)) if !self.is_expired(inserted) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value corresponding to the given
+    /// key without refreshing its LRU position, or `None` if it's not
+    /// present or has expired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.insert(1, 10);
+    /// *cache.peek_mut(&1).unwrap() += 1;
+    /// assert_eq!(cache.peek(&1), Some(&11));
+    /// ```
+    pub fn peek_mut(&mut self, k: &K) -> Option<&mut V> {
+        let expired = match self.map.get(k) {
+            Some((inserted, _, _)) => self.is_expired(inserted),
+            None => return None,
+        };
+        if expired {
+            self.remove(k);
+            return None;
+        }
+        self.map.get_mut(k).map(|&mut (_, ref mut v, _)| v)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the given
+    /// key, refreshing its LRU position, or `None` if it's not present or
+    /// has expired.
+    ///
+    /// Note that mutating the value through the returned reference bypasses
+    /// the size accounting used by [`LruCache::with_memory_limit`]; use
+    /// [`LruCache::mutate`] instead under that mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// cache.insert(1, 10);
+    /// *cache.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(cache.get(&1), Some(&11));
+    /// ```
+    pub fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        let expired = match self.map.get(k) {
+            Some((inserted, _, _)) => self.is_expired(inserted),
+            None => return None,
+        };
+        if expired {
+            self.remove(k);
+            return None;
+        }
+        self.map.get_refresh(k);
+        self.map.get_mut(k).map(|&mut (_, ref mut v, _)| v)
+    }
+
+    /// Returns a mutable reference to the value corresponding to `k`,
+    /// refreshing its LRU position, inserting one produced by `f` first if
+    /// it wasn't already present or had expired. Either way, the normal
+    /// eviction check runs afterward, so this returns `None` if the cache
+    /// has zero capacity and the freshly inserted entry was evicted
+    /// immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(2);
+    /// *cache.get_or_insert_with(1, || 10).unwrap() += 1;
+    /// assert_eq!(*cache.get_or_insert_with(1, || 0).unwrap(), 11);
+    /// ```
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, k: K, f: F) -> Option<&mut V>
+    where
+        K: Clone,
+    {
+        if !self.contains_key(&k) {
+            self.insert(k.clone(), f());
+        }
+        self.get_mut(&k)
+    }
+
+    /// Removes every entry that has exceeded the cache's expiry duration.
+    ///
+    /// This sweeps the whole cache up front, unlike the lazy removal
+    /// performed by [`LruCache::get`], [`LruCache::get_mut`] and
+    /// [`LruCache::peek_mut`], so callers can reclaim the memory held by
+    /// stale entries without having to look each of them up first.
+    /// [`LruCache::contains_key`] and [`LruCache::peek`] report an expired
+    /// entry as absent but do not remove it. Does nothing if the cache was
+    /// not created with an expiry duration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread::sleep;
+    /// use std::time::Duration;
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::with_expiry_duration(10, Duration::from_millis(1));
+    /// cache.insert(1, "a");
+    /// sleep(Duration::from_millis(10));
+    /// cache.remove_expired();
+    /// assert_eq!(cache.len(), 0);
+    /// ```
+    pub fn remove_expired(&mut self) where K: Clone {
+        let expiry_duration = match self.expiry_duration {
+            Some(duration) => duration,
+            None => return,
+        };
+        let expired_keys: Vec<K> = self.map.iter()
+            .filter(|&(_, &(inserted, _, _))| inserted.elapsed() > expiry_duration)
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        for k in expired_keys {
+            self.remove(&k);
+        }
     }
 
     /// Removes the given key from the cache and returns its corresponding value.
@@ -132,8 +499,6 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     /// # Examples
     ///
     /// ```
-    /// # extern crate "lru-cache" as lru_cache;
-    /// # fn main() {
     /// use lru_cache::LruCache;
     ///
     /// let mut cache = LruCache::new(2);
@@ -144,10 +509,12 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     /// assert_eq!(cache.remove(&2), Some("a"));
     /// assert_eq!(cache.remove(&2), None);
     /// assert_eq!(cache.len(), 0);
-    /// # }
     /// ```
     pub fn remove(&mut self, k: &K) -> Option<V> {
-        self.map.remove(k)
+        self.map.remove(k).map(|(_, v, size)| {
+            self.mem_usage -= size;
+            v
+        })
     }
 
     /// Returns the maximum number of key-value pairs the cache can hold.
@@ -155,12 +522,9 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     /// # Examples
     ///
     /// ```
-    /// # extern crate "lru-cache" as lru_cache;
-    /// # fn main() {
     /// use lru_cache::LruCache;
     /// let mut cache: LruCache<i32, &str> = LruCache::new(2);
     /// assert_eq!(cache.capacity(), 2);
-    /// # }
     /// ```
     pub fn capacity(&self) -> usize {
         self.max_size
@@ -172,8 +536,6 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     /// # Examples
     ///
     /// ```
-    /// # extern crate "lru-cache" as lru_cache;
-    /// # fn main() {
     /// use lru_cache::LruCache;
     ///
     /// let mut cache = LruCache::new(2);
@@ -199,7 +561,6 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     /// assert_eq!(cache.get(&1), None);
     /// assert_eq!(cache.get(&2), None);
     /// assert_eq!(cache.get(&3), Some(&"c"));
-    /// # }
     /// ```
     pub fn set_capacity(&mut self, capacity: usize) {
         for _ in capacity..self.len() {
@@ -209,8 +570,10 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     }
 
     #[inline]
-    fn remove_lru(&mut self) {
-        self.map.pop_front()
+    pub(crate) fn remove_lru(&mut self) {
+        if let Some((_, (_, _, size))) = self.map.pop_front() {
+            self.mem_usage -= size;
+        }
     }
 
     /// Returns the number of key-value pairs in the cache.
@@ -220,10 +583,68 @@ impl<K: Hash + Eq, V> LruCache<K, V> {
     pub fn is_empty(&self) -> bool { self.map.is_empty() }
 
     /// Removes all key-value pairs from the cache.
-    pub fn clear(&mut self) { self.map.clear(); }
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.mem_usage = 0;
+    }
+
+    /// Returns an iterator over the cache's entries, from most-recently-used
+    /// to least-recently-used. Iterating does not count as a "use", so it
+    /// does not refresh LRU order, unlike `get`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::LruCache;
+    ///
+    /// let mut cache = LruCache::new(3);
+    /// cache.insert(1, 10);
+    /// cache.insert(2, 20);
+    /// cache.insert(3, 30);
+    ///
+    /// let items: Vec<_> = cache.iter().collect();
+    /// assert_eq!(items, vec![(&3, &30), (&2, &20), (&1, &10)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { inner: self.map.iter().rev() }
+    }
+
+    /// Returns an iterator over the cache's entries with mutable value
+    /// references, from most-recently-used to least-recently-used. Like
+    /// [`LruCache::iter`], this does not refresh LRU order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut { inner: self.map.iter_mut().rev() }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> IntoIterator for LruCache<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter { inner: self.map.into_iter().rev() }
+    }
 }
 
-impl<K: Hash + Eq, V> Extend<(K, V)> for LruCache<K, V> {
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a LruCache<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a mut LruCache<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for LruCache<K, V, S> {
     fn extend<T: IntoIterator<Item=(K, V)>>(&mut self, iter: T) {
         for (k, v) in iter {
             self.insert(k, v);
@@ -231,13 +652,13 @@ impl<K: Hash + Eq, V> Extend<(K, V)> for LruCache<K, V> {
     }
 }
 
-impl<A: fmt::Debug + Hash + Eq, B: fmt::Debug> fmt::Debug for LruCache<A, B> {
+impl<A: fmt::Debug + Hash + Eq, B: fmt::Debug, S: BuildHasher> fmt::Debug for LruCache<A, B, S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        try!(write!(f, "{{"));
+        write!(f, "{{")?;
 
-        for (i, (k, v)) in self.map.iter().rev().enumerate() {
-            if i != 0 { try!(write!(f, ", ")); }
-            try!(write!(f, "{:?}: {:?}", *k, *v));
+        for (i, (k, (_, v, _))) in self.map.iter().rev().enumerate() {
+            if i != 0 { write!(f, ", ")?; }
+            write!(f, "{:?}: {:?}", *k, *v)?;
         }
 
         write!(f, "}}")
@@ -247,6 +668,8 @@ impl<A: fmt::Debug + Hash + Eq, B: fmt::Debug> fmt::Debug for LruCache<A, B> {
 #[cfg(test)]
 mod tests {
     use super::LruCache;
+    use std::thread::sleep;
+    use std::time::Duration;
 
     fn assert_opt_eq<V: PartialEq>(opt: Option<&V>, v: V) {
         assert!(opt.is_some());
@@ -263,6 +686,32 @@ mod tests {
         assert_eq!(cache.len(), 2);
     }
 
+    #[test]
+    fn test_with_hasher_uses_custom_hash_builder() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasher;
+
+        #[derive(Clone, Default)]
+        struct DeterministicHasher;
+
+        impl BuildHasher for DeterministicHasher {
+            type Hasher = DefaultHasher;
+
+            fn build_hasher(&self) -> DefaultHasher {
+                DefaultHasher::new()
+            }
+        }
+
+        let mut cache = LruCache::with_hasher(2, DeterministicHasher);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        assert_opt_eq(cache.get(&1), 10);
+        assert_opt_eq(cache.get(&2), 20);
+        cache.insert(3, 30);
+        assert!(cache.get(&1).is_none());
+        assert_opt_eq(cache.get(&3), 30);
+    }
+
     #[test]
     fn test_put_update() {
         let mut cache: LruCache<String, Vec<u8>> = LruCache::new(1);
@@ -356,4 +805,172 @@ mod tests {
         assert!(cache.get(&2).is_none());
         assert_eq!(format!("{:?}", cache), "{}");
     }
+
+    #[test]
+    fn test_expiry() {
+        let mut cache = LruCache::with_expiry_duration(10, Duration::from_millis(20));
+        cache.insert(1, "a");
+        assert_opt_eq(cache.get(&1), "a");
+        sleep(Duration::from_millis(40));
+        assert!(cache.get(&1).is_none());
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn test_contains_key_respects_expiry() {
+        let mut cache = LruCache::with_expiry_duration(10, Duration::from_millis(20));
+        cache.insert(1, "a");
+        assert!(cache.contains_key(&1));
+        sleep(Duration::from_millis(40));
+        assert!(!cache.contains_key(&1));
+    }
+
+    #[test]
+    fn test_memory_limit_evicts_lru() {
+        // Each (i32, i32) entry estimates to 8 bytes, so a 24-byte budget holds 3.
+        let mut cache: LruCache<i32, i32> = LruCache::with_memory_limit(24);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        cache.insert(3, 30);
+        assert_eq!(cache.len(), 3);
+        cache.insert(4, 40);
+        assert!(cache.get(&1).is_none());
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn test_clear_resets_memory_usage() {
+        // Each (i32, i32) entry estimates to 8 bytes, so a 24-byte budget holds 3.
+        let mut cache: LruCache<i32, i32> = LruCache::with_memory_limit(24);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        cache.insert(3, 30);
+        cache.clear();
+        // If mem_usage were not reset, these inserts would evict prematurely
+        // and the cache would never refill to 3 entries.
+        cache.insert(4, 40);
+        cache.insert(5, 50);
+        cache.insert(6, 60);
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn test_mutate_rechecks_memory_limit() {
+        let mut cache: LruCache<i32, String> = LruCache::with_memory_limit(64);
+        cache.insert(1, "a".to_string());
+        cache.insert(2, "b".to_string());
+        cache.mutate(&1, |v| v.push_str(&"x".repeat(64)));
+        // Growing entry 1 past the limit should evict the least-recently-used entry.
+        assert!(cache.get(&2).is_none());
+        assert!(cache.contains_key(&1));
+    }
+
+    #[test]
+    fn test_iter_order() {
+        let mut cache = LruCache::new(3);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        cache.insert(3, 30);
+        cache.get(&1);
+        let items: Vec<_> = cache.iter().collect();
+        assert_eq!(items, vec![(&1, &10), (&3, &30), (&2, &20)]);
+    }
+
+    #[test]
+    fn test_iter_does_not_refresh_order() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        // Walking the whole cache is not a "use".
+        for _ in cache.iter() {}
+        cache.insert(3, 30);
+        assert!(cache.get(&1).is_none());
+        assert_opt_eq(cache.get(&2), 20);
+        assert_opt_eq(cache.get(&3), 30);
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        for (_, v) in cache.iter_mut() {
+            *v += 1;
+        }
+        assert_opt_eq(cache.get(&1), 11);
+        assert_opt_eq(cache.get(&2), 21);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let mut cache = LruCache::new(3);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        cache.insert(3, 30);
+        let items: Vec<_> = cache.into_iter().collect();
+        assert_eq!(items, vec![(3, 30), (2, 20), (1, 10)]);
+    }
+
+    #[test]
+    fn test_peek_does_not_refresh_order() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        assert_opt_eq(cache.peek(&1), 10);
+        cache.insert(3, 30);
+        // Peeking 1 didn't refresh it, so it was still the LRU entry.
+        assert!(cache.peek(&1).is_none());
+        assert_opt_eq(cache.peek(&2), 20);
+        assert_opt_eq(cache.peek(&3), 30);
+    }
+
+    #[test]
+    fn test_peek_mut() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, 10);
+        *cache.peek_mut(&1).unwrap() += 1;
+        assert_eq!(cache.peek(&1), Some(&11));
+        assert!(cache.peek_mut(&2).is_none());
+    }
+
+    #[test]
+    fn test_get_mut_refreshes_order() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        *cache.get_mut(&1).unwrap() += 1;
+        cache.insert(3, 30);
+        // get_mut refreshed 1, so 2 was the LRU entry and got evicted instead.
+        assert!(cache.get(&2).is_none());
+        assert_opt_eq(cache.get(&1), 11);
+        assert_opt_eq(cache.get(&3), 30);
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(*cache.get_or_insert_with(1, || 10).unwrap(), 10);
+        *cache.get_or_insert_with(1, || 0).unwrap() += 1;
+        assert_eq!(cache.get(&1), Some(&11));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_get_or_insert_with_zero_capacity() {
+        let mut cache: LruCache<i32, i32> = LruCache::new(0);
+        assert!(cache.get_or_insert_with(1, || 10).is_none());
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn test_remove_expired() {
+        let mut cache = LruCache::with_expiry_duration(10, Duration::from_millis(20));
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        sleep(Duration::from_millis(40));
+        cache.insert(3, "c");
+        cache.remove_expired();
+        assert_eq!(cache.len(), 1);
+        assert_opt_eq(cache.get(&3), "c");
+    }
 }
@@ -0,0 +1,91 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Size estimation for [`LruCache::with_memory_limit`](crate::LruCache::with_memory_limit).
+
+/// Types that can estimate their own memory footprint, in bytes.
+///
+/// `LruCache::with_memory_limit` sums `mem_size()` across every live key
+/// and value and evicts least-recently-used entries until the running
+/// total is back under the configured limit (the last remaining entry is
+/// never evicted, even if it alone exceeds the limit). Implementations are only
+/// required to be reasonably close; they don't need to account for
+/// allocator overhead or alignment padding.
+pub trait MemSize {
+    /// Returns an estimate of the memory this value occupies.
+    fn mem_size(&self) -> usize;
+}
+
+macro_rules! mem_size_by_value {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl MemSize for $t {
+                fn mem_size(&self) -> usize {
+                    std::mem::size_of::<$t>()
+                }
+            }
+        )*
+    };
+}
+
+mem_size_by_value!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char);
+
+impl MemSize for String {
+    fn mem_size(&self) -> usize {
+        std::mem::size_of::<String>() + self.capacity()
+    }
+}
+
+impl MemSize for &str {
+    fn mem_size(&self) -> usize {
+        std::mem::size_of::<&str>() + self.len()
+    }
+}
+
+impl<T: MemSize> MemSize for Vec<T> {
+    fn mem_size(&self) -> usize {
+        std::mem::size_of::<Vec<T>>() + self.iter().map(MemSize::mem_size).sum::<usize>()
+    }
+}
+
+impl<T: MemSize> MemSize for Box<T> {
+    fn mem_size(&self) -> usize {
+        std::mem::size_of::<Box<T>>() + (**self).mem_size()
+    }
+}
+
+impl<T: MemSize> MemSize for Option<T> {
+    fn mem_size(&self) -> usize {
+        std::mem::size_of::<Option<T>>() + self.as_ref().map_or(0, MemSize::mem_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemSize;
+
+    #[test]
+    fn test_primitive_sizes() {
+        assert_eq!(0u32.mem_size(), 4);
+        assert_eq!(0u64.mem_size(), 8);
+    }
+
+    #[test]
+    fn test_string_size_tracks_capacity() {
+        let s = String::with_capacity(16);
+        assert_eq!(s.mem_size(), std::mem::size_of::<String>() + 16);
+    }
+
+    #[test]
+    fn test_vec_size_sums_elements() {
+        let v: Vec<u32> = vec![1, 2, 3];
+        assert_eq!(v.mem_size(), std::mem::size_of::<Vec<u32>>() + 12);
+    }
+}
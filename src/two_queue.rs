@@ -0,0 +1,227 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A scan-resistant alternative to [`LruCache`](crate::LruCache), implementing
+//! the 2Q replacement policy.
+
+use std::hash::Hash;
+
+use linked_hash_map::LinkedHashMap;
+
+/// A cache implementing the 2Q replacement policy.
+///
+/// Plain LRU evicts purely by recency, so a single one-shot scan over many
+/// keys can flush out entries that are genuinely hot. 2Q guards against
+/// this with three structures: `a1in`, a small FIFO holding recently
+/// inserted values; `a1out`, a FIFO of *keys only* that were evicted from
+/// `a1in`; and `am`, a full LRU list for values that have proven hot. A key
+/// only earns a spot in `am` once it has been seen twice -- recognized via
+/// a hit against the `a1out` ghost list on its second insert -- so a scan
+/// that touches every key exactly once never displaces anything in `am`.
+///
+/// # Examples
+///
+/// ```
+/// use lru_cache::TwoQueueCache;
+///
+/// let mut cache = TwoQueueCache::new(4);
+/// cache.insert(1, "a");
+/// assert_eq!(cache.get(&1), Some(&"a"));
+/// ```
+pub struct TwoQueueCache<K, V> where K: Eq + Hash {
+    a1in: LinkedHashMap<K, V>,
+    a1out: LinkedHashMap<K, ()>,
+    am: LinkedHashMap<K, V>,
+    capacity: usize,
+    kin: usize,
+    kout: usize,
+}
+
+impl<K: Hash + Eq, V> TwoQueueCache<K, V> {
+    /// Creates an empty cache that can hold at most `capacity` items, with
+    /// `a1in` bounded to ~25% of `capacity` and the `a1out` ghost list
+    /// bounded to ~50% of `capacity`.
+    pub fn new(capacity: usize) -> TwoQueueCache<K, V> {
+        TwoQueueCache::with_queue_sizes(capacity, capacity / 4, capacity / 2)
+    }
+
+    /// Creates an empty cache with explicit bounds for the `a1in` recency
+    /// queue (`kin`) and the `a1out` ghost queue (`kout`), instead of the
+    /// ~25%/~50% defaults used by [`TwoQueueCache::new`].
+    pub fn with_queue_sizes(capacity: usize, kin: usize, kout: usize) -> TwoQueueCache<K, V> {
+        TwoQueueCache {
+            a1in: LinkedHashMap::new(),
+            a1out: LinkedHashMap::new(),
+            am: LinkedHashMap::new(),
+            capacity,
+            kin,
+            kout,
+        }
+    }
+
+    /// Returns the value corresponding to the given key in the cache.
+    ///
+    /// A hit in `am` refreshes its LRU position, same as `LruCache::get`. A
+    /// hit in `a1in` is returned without disturbing its FIFO position --
+    /// `a1in` tracks arrival order, not read frequency.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(4);
+    /// cache.insert(1, "a");
+    /// assert_eq!(cache.get(&1), Some(&"a"));
+    /// assert_eq!(cache.get(&2), None);
+    /// ```
+    pub fn get(&mut self, k: &K) -> Option<&V> {
+        if self.am.contains_key(k) {
+            return self.am.get_refresh(k).map(|v| &*v);
+        }
+        self.a1in.get(k)
+    }
+
+    /// Inserts a key-value pair into the cache. If the key already existed,
+    /// the old value is returned.
+    ///
+    /// A key making its second appearance -- found in the `a1out` ghost
+    /// list -- is promoted straight into `am`, since a repeat insert is
+    /// evidence it's hot rather than part of a one-off scan. Otherwise it
+    /// only earns a spot in `a1in`, where it may later be evicted into
+    /// `a1out` without ever reaching `am`.
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if self.am.contains_key(&k) {
+            return self.am.insert(k, v);
+        }
+        if self.a1in.contains_key(&k) {
+            return self.a1in.insert(k, v);
+        }
+        if self.a1out.remove(&k).is_some() {
+            self.am.insert(k, v);
+            self.evict_am();
+            return None;
+        }
+        self.a1in.insert(k, v);
+        self.evict_a1in();
+        None
+    }
+
+    /// Removes the given key from the cache and returns its corresponding value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lru_cache::TwoQueueCache;
+    ///
+    /// let mut cache = TwoQueueCache::new(4);
+    /// cache.insert(1, "a");
+    /// assert_eq!(cache.remove(&1), Some("a"));
+    /// assert_eq!(cache.remove(&1), None);
+    /// ```
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        if let Some(v) = self.am.remove(k) {
+            return Some(v);
+        }
+        if let Some(v) = self.a1in.remove(k) {
+            return Some(v);
+        }
+        self.a1out.remove(k);
+        None
+    }
+
+    /// Returns the number of key-value pairs in the cache.
+    ///
+    /// Ghost entries in `a1out` hold no value and are not counted.
+    pub fn len(&self) -> usize {
+        self.am.len() + self.a1in.len()
+    }
+
+    /// Returns `true` if the cache contains no key-value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pops `am`'s LRU tail while it exceeds its share of `capacity`.
+    fn evict_am(&mut self) {
+        let am_capacity = self.capacity.saturating_sub(self.kin);
+        while self.am.len() > am_capacity {
+            self.am.pop_front();
+        }
+    }
+
+    /// Pops `a1in`'s tail into the `a1out` ghost list while it exceeds `kin`.
+    fn evict_a1in(&mut self) {
+        while self.a1in.len() > self.kin {
+            if let Some((k, _)) = self.a1in.pop_front() {
+                self.a1out.insert(k, ());
+            }
+        }
+        self.evict_a1out();
+    }
+
+    /// Discards `a1out`'s tail while it exceeds `kout`.
+    fn evict_a1out(&mut self) {
+        while self.a1out.len() > self.kout {
+            self.a1out.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TwoQueueCache;
+
+    #[test]
+    fn test_first_touch_stays_in_a1in() {
+        let mut cache = TwoQueueCache::with_queue_sizes(8, 2, 4);
+        cache.insert(1, "a");
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_second_insert_promotes_to_am() {
+        let mut cache = TwoQueueCache::with_queue_sizes(8, 1, 4);
+        cache.insert(1, "a");
+        // Evict 1 out of the tiny a1in queue and into the a1out ghost list.
+        cache.insert(2, "b");
+        assert_eq!(cache.get(&1), None);
+        // Reinserting a key remembered by a1out promotes it straight to `am`.
+        cache.insert(1, "a2");
+        assert_eq!(cache.get(&1), Some(&"a2"));
+    }
+
+    #[test]
+    fn test_scan_does_not_evict_hot_entry() {
+        let mut cache = TwoQueueCache::with_queue_sizes(8, 1, 4);
+        cache.insert(1, "a");
+        // Evict 1 out of the tiny a1in queue and into the a1out ghost list.
+        cache.insert(2, "b");
+        // Reinserting a key remembered by a1out promotes it straight to `am`.
+        cache.insert(1, "a2");
+        assert_eq!(cache.get(&1), Some(&"a2"));
+
+        // A one-shot scan through a1in only should never touch `am`.
+        for i in 100..110 {
+            cache.insert(i, "scan");
+        }
+        assert_eq!(cache.get(&1), Some(&"a2"));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut cache = TwoQueueCache::new(4);
+        cache.insert(1, "a");
+        assert_eq!(cache.remove(&1), Some("a"));
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.get(&1), None);
+    }
+}